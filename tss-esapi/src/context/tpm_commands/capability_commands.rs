@@ -2,15 +2,90 @@
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
     constants::CapabilityType,
-    structures::{CapabilityData, PublicParameters},
+    structures::{
+        AlgorithmPropertyList, CapabilityData, EccCurveList, HashingAlgorithm, PcrSelect,
+        PropertyTag, PublicParameters,
+    },
     tss2_esys::*,
-    Context, Error, Result, WrapperErrorKind as ErrorKind,
+    Context, Error, Result, Tss2ResponseCode, Tss2ResponseCodeKind, WrapperErrorKind as ErrorKind,
 };
 use log::{error, warn};
 use mbox::MBox;
 use std::convert::TryFrom;
 use std::ptr::null_mut;
 
+/// A high-level summary of the TPM that is being communicated with.
+///
+/// This is assembled by [Context::get_tpm_info] from several
+/// [Context::get_capability]/[Context::get_tpm_property] calls, decoding
+/// the manufacturer, vendor string and firmware version from the raw
+/// property values the TPM reports them as.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TpmInfo {
+    manufacturer: String,
+    vendor_string: String,
+    firmware_version: u64,
+    spec_revision: f32,
+    spec_level: u32,
+    supported_algorithms: AlgorithmPropertyList,
+    supported_ecc_curves: EccCurveList,
+}
+
+impl TpmInfo {
+    /// The ASCII manufacturer code reported by the TPM.
+    ///
+    /// This is at most 4 characters long: null bytes padding a shorter
+    /// code (e.g. `IBM`) are not included.
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    /// The vendor-specific string describing this TPM.
+    pub fn vendor_string(&self) -> &str {
+        &self.vendor_string
+    }
+
+    /// The vendor-specific firmware version running on the TPM.
+    pub fn firmware_version(&self) -> u64 {
+        self.firmware_version
+    }
+
+    /// The revision of the TPM specification the TPM implements, e.g.
+    /// `1.46`.
+    ///
+    /// The TPM reports this as the revision multiplied by 100 (`146`
+    /// for revision `1.46`); this is already divided back down.
+    pub fn spec_revision(&self) -> f32 {
+        self.spec_revision
+    }
+
+    /// The level of the TPM specification the TPM implements.
+    pub fn spec_level(&self) -> u32 {
+        self.spec_level
+    }
+
+    /// The algorithms supported by the TPM.
+    pub fn supported_algorithms(&self) -> &AlgorithmPropertyList {
+        &self.supported_algorithms
+    }
+
+    /// The ECC curves supported by the TPM.
+    pub fn supported_ecc_curves(&self) -> &EccCurveList {
+        &self.supported_ecc_curves
+    }
+}
+
+/// Decodes a TPM property value that packs up to 4 ASCII characters into
+/// a `u32`, big-endian, skipping any trailing null bytes.
+fn decode_ascii_property(value: u32) -> String {
+    value
+        .to_be_bytes()
+        .iter()
+        .take_while(|&&byte| byte != 0)
+        .map(|&byte| byte as char)
+        .collect()
+}
+
 impl Context {
     /// Get current capability information about the TPM.
     ///
@@ -81,6 +156,189 @@ impl Context {
         }
     }
 
+    /// Get current capability information about the TPM, following the
+    /// `moreData` flag until the TPM has no more data left to return.
+    ///
+    /// Unlike [Context::get_capability], which returns at most a single
+    /// page of results bounded by the caller's `property_count`, this method
+    /// transparently advances the starting `property` between calls and
+    /// merges the pages it receives into a single [CapabilityData] value.
+    ///
+    /// `property` is passed straight through to the first
+    /// [Context::get_capability] call, so it keeps the same meaning as
+    /// there: for most capabilities it is the identifier to start
+    /// enumerating from, but for [CapabilityType::Handles] its
+    /// most-significant octet selects which handle range (transient,
+    /// persistent, NV, session, PCR, ...) is enumerated. Pass `0` to get
+    /// the TPM's default starting point for non-handle capabilities.
+    ///
+    /// # Errors
+    /// * if the TPM reports that more data is available but a page does
+    /// not advance past the previously seen starting property, a
+    /// [ErrorKind::WrongValueFromTpm] error is returned instead of
+    /// looping forever.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use tss_esapi::{Context, TctiNameConf};
+    /// # // Create context
+    /// # let mut context =
+    /// #     Context::new(
+    /// #         TctiNameConf::from_environment_variable().expect("Failed to get TCTI"),
+    /// #     ).expect("Failed to create Context");
+    /// #
+    /// use tss_esapi::constants::CapabilityType;
+    ///
+    /// let _capabilities = context
+    ///     .get_capability_all(CapabilityType::Algorithms, 0)
+    ///     .expect("Failed to call get_capability_all");
+    /// ```
+    pub fn get_capability_all(
+        &mut self,
+        capability: CapabilityType,
+        property: u32,
+    ) -> Result<CapabilityData> {
+        // Large enough to keep the number of round trips small without
+        // risking an oversized marshalled response from the TPM.
+        const PROPERTY_COUNT: u32 = 128;
+
+        let mut property = property;
+        let (mut capabilities, mut more_data) =
+            self.get_capability(capability, property, PROPERTY_COUNT)?;
+
+        while more_data {
+            let next_property = next_capability_property(&capabilities)?;
+            if next_property <= property {
+                error!(
+                    "TPM did not advance the starting property while reporting more capability data"
+                );
+                return Err(Error::WrapperError(ErrorKind::WrongValueFromTpm));
+            }
+            property = next_property;
+
+            let (page, page_more_data) = self.get_capability(capability, property, PROPERTY_COUNT)?;
+            capabilities = merge_capability_data(capabilities, page)?;
+            more_data = page_more_data;
+        }
+
+        Ok(capabilities)
+    }
+
+    /// Get the value of a single TPM property.
+    ///
+    /// This is a convenience wrapper around [Context::get_capability] for
+    /// the common case of looking up one [PropertyTag]: it performs the
+    /// call with `property` as the starting point and a `property_count`
+    /// of 1, then extracts the matching value from the returned
+    /// [CapabilityData::TpmProperties] list, removing the need for
+    /// callers to match on `CapabilityData` and search the result
+    /// themselves.
+    ///
+    /// Returns `Ok(None)` if the TPM has no value for the requested
+    /// property.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use tss_esapi::{Context, TctiNameConf};
+    /// # // Create context
+    /// # let mut context =
+    /// #     Context::new(
+    /// #         TctiNameConf::from_environment_variable().expect("Failed to get TCTI"),
+    /// #     ).expect("Failed to create Context");
+    /// #
+    /// use tss_esapi::structures::PropertyTag;
+    ///
+    /// let _revision = context
+    ///     .get_tpm_property(PropertyTag::Revision)
+    ///     .expect("Failed to call get_tpm_property");
+    /// ```
+    pub fn get_tpm_property(&mut self, property: PropertyTag) -> Result<Option<u32>> {
+        let (capabilities, _) =
+            self.get_capability(CapabilityType::TpmProperties, property.into(), 1)?;
+
+        match capabilities {
+            CapabilityData::TpmProperties(properties) => Ok(properties
+                .iter()
+                .find(|tagged_property| tagged_property.property() == property)
+                .map(|tagged_property| tagged_property.value())),
+            _ => {
+                error!("TPM returned capability data of an unexpected type");
+                Err(Error::WrapperError(ErrorKind::WrongValueFromTpm))
+            }
+        }
+    }
+
+    /// Get a high-level summary of the TPM being communicated with.
+    ///
+    /// This aggregates several [Context::get_capability] and
+    /// [Context::get_tpm_property] calls into one [TpmInfo] value,
+    /// decoding the manufacturer and vendor string from their packed
+    /// ASCII representation and combining the two firmware version
+    /// properties into a single `u64`, instead of requiring callers to
+    /// assemble these values byte-by-byte themselves.
+    ///
+    /// # Errors
+    /// * if the TPM does not report a value for one of the fixed
+    /// properties that make up a [TpmInfo], a
+    /// [ErrorKind::WrongValueFromTpm] error is returned.
+    pub fn get_tpm_info(&mut self) -> Result<TpmInfo> {
+        let fixed_property = |context: &mut Self, tag: PropertyTag| -> Result<u32> {
+            context.get_tpm_property(tag)?.ok_or_else(|| {
+                error!("TPM did not report a value for {:?}", tag);
+                Error::WrapperError(ErrorKind::WrongValueFromTpm)
+            })
+        };
+
+        let manufacturer = decode_ascii_property(fixed_property(self, PropertyTag::Manufacturer)?);
+
+        let vendor_string = [
+            PropertyTag::VendorString1,
+            PropertyTag::VendorString2,
+            PropertyTag::VendorString3,
+            PropertyTag::VendorString4,
+        ]
+        .into_iter()
+        .map(|tag| fixed_property(self, tag).map(decode_ascii_property))
+        .collect::<Result<Vec<String>>>()?
+        .concat();
+
+        let firmware_version_1 = fixed_property(self, PropertyTag::FirmwareVersion1)?;
+        let firmware_version_2 = fixed_property(self, PropertyTag::FirmwareVersion2)?;
+        let firmware_version =
+            (u64::from(firmware_version_1) << 32) | u64::from(firmware_version_2);
+
+        let spec_revision = fixed_property(self, PropertyTag::Revision)? as f32 / 100.0;
+        let spec_level = fixed_property(self, PropertyTag::Level)?;
+
+        let supported_algorithms = match self.get_capability_all(CapabilityType::Algorithms, 0)? {
+            CapabilityData::Algorithms(algorithms) => algorithms,
+            _ => {
+                error!("TPM returned capability data of an unexpected type");
+                return Err(Error::WrapperError(ErrorKind::WrongValueFromTpm));
+            }
+        };
+
+        let supported_ecc_curves = match self.get_capability_all(CapabilityType::EccCurves, 0)? {
+            CapabilityData::EccCurves(ecc_curves) => ecc_curves,
+            _ => {
+                error!("TPM returned capability data of an unexpected type");
+                return Err(Error::WrapperError(ErrorKind::WrongValueFromTpm));
+            }
+        };
+
+        Ok(TpmInfo {
+            manufacturer,
+            vendor_string,
+            firmware_version,
+            spec_revision,
+            spec_level,
+            supported_algorithms,
+            supported_ecc_curves,
+        })
+    }
+
     /// Test if the given parameters are supported by the TPM.
     ///
     /// # Errors
@@ -105,4 +363,178 @@ impl Context {
             Err(ret)
         }
     }
+
+    /// Check whether the given public parameters are supported by the TPM.
+    ///
+    /// This wraps [Context::test_parms], translating the TPM's "parameter
+    /// not supported" response into `Ok(false)` rather than an `Err`, so
+    /// that callers can use it to probe the TPM's capabilities without
+    /// having to treat "unsupported" and a genuine failure the same way.
+    /// Any other error is still propagated.
+    pub fn are_parms_supported(&mut self, parms: PublicParameters) -> Result<bool> {
+        match self.test_parms(parms) {
+            Ok(()) => Ok(true),
+            Err(Error::Tss2Error(tss2_error)) if is_unsupported_parms_error(tss2_error) => {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Find the first of the given candidate public parameter sets that
+    /// is supported by the TPM.
+    ///
+    /// This is useful for negotiating a key type with the TPM: callers
+    /// supply candidates in order of preference (for example RSA-3072,
+    /// then RSA-2048, then ECC P-256) and get back the first one the TPM
+    /// accepts, or `None` if none of them are supported.
+    pub fn first_supported_parms(
+        &mut self,
+        candidates: impl IntoIterator<Item = PublicParameters>,
+    ) -> Result<Option<PublicParameters>> {
+        for candidate in candidates {
+            if self.are_parms_supported(candidate.clone())? {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Discover the PCR banks currently allocated on the TPM.
+    ///
+    /// Calls [Context::get_capability_all] for [CapabilityType::AssignedPcr]
+    /// and decodes the returned [CapabilityData::AssignedPcr] selection
+    /// list into the hashing algorithm and set of allocated PCR indices
+    /// for each active bank, instead of leaving callers to parse the raw
+    /// `TPMS_PCR_SELECTION` structures themselves.
+    pub fn get_pcr_banks(&mut self) -> Result<Vec<(HashingAlgorithm, PcrSelect)>> {
+        match self.get_capability_all(CapabilityType::AssignedPcr, 0)? {
+            CapabilityData::AssignedPcr(pcr_selection_list) => Ok(pcr_selection_list
+                .iter()
+                .map(|pcr_selection| {
+                    (
+                        pcr_selection.hashing_algorithm(),
+                        pcr_selection.pcr_select().clone(),
+                    )
+                })
+                .collect()),
+            _ => {
+                error!("TPM returned capability data of an unexpected type");
+                Err(Error::WrapperError(ErrorKind::WrongValueFromTpm))
+            }
+        }
+    }
+}
+
+/// Computes the starting `property` to use for the next page of a
+/// [CapabilityType] enumeration, based on the last entry seen in the
+/// previous page.
+fn next_capability_property(capabilities: &CapabilityData) -> Result<u32> {
+    match capabilities {
+        CapabilityData::AssignedPcr(pcr_selections) => pcr_selections
+            .iter()
+            .last()
+            .map(|pcr_selection| u32::from(u16::from(pcr_selection.hashing_algorithm())) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::Handles(handles) => handles
+            .iter()
+            .last()
+            .map(|handle| u32::from(*handle) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::Algorithms(algorithms) => algorithms
+            .iter()
+            .last()
+            .map(|algorithm_property| u32::from(u16::from(algorithm_property.algorithm())) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::Command(commands) => commands
+            .iter()
+            .last()
+            .map(|command_code| u32::from(*command_code) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::EccCurves(ecc_curves) => ecc_curves
+            .iter()
+            .last()
+            .map(|ecc_curve| u32::from(u16::from(*ecc_curve)) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::TpmProperties(properties) => properties
+            .iter()
+            .last()
+            .map(|tagged_property| u32::from(tagged_property.property()) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        CapabilityData::PcrProperties(properties) => properties
+            .iter()
+            .last()
+            .map(|tagged_pcr_select| u32::from(tagged_pcr_select.tag()) + 1)
+            .ok_or(Error::WrapperError(ErrorKind::WrongValueFromTpm)),
+        _ => {
+            error!("Auto-pagination is not supported for this capability type");
+            Err(Error::WrapperError(ErrorKind::WrongValueFromTpm))
+        }
+    }
+}
+
+/// Whether a TSS2 response code is one of the FMT1 "bad parameter value"
+/// kinds `Esys_TestParms` uses to reject an unsupported field, rather
+/// than some other kind of failure.
+///
+/// The TPM does not have a single dedicated "unsupported" response code:
+/// it reports whichever FMT1 code matches the invalid field, e.g.
+/// `TPM_RC_CURVE` for an unsupported ECC curve or `TPM_RC_SCHEME` for an
+/// unsupported signing scheme.
+fn is_unsupported_parms_error(tss2_error: Tss2ResponseCode) -> bool {
+    matches!(
+        tss2_error.kind(),
+        Some(
+            Tss2ResponseCodeKind::Value
+                | Tss2ResponseCodeKind::Size
+                | Tss2ResponseCodeKind::Type
+                | Tss2ResponseCodeKind::Mode
+                | Tss2ResponseCodeKind::Hash
+                | Tss2ResponseCodeKind::KeySize
+                | Tss2ResponseCodeKind::Scheme
+                | Tss2ResponseCodeKind::Symmetric
+                | Tss2ResponseCodeKind::Curve
+                | Tss2ResponseCodeKind::Asymmetric
+        )
+    )
+}
+
+/// Merges two pages of [CapabilityData] returned for the same
+/// [CapabilityType] into a single value, preserving the order in which
+/// they were received.
+fn merge_capability_data(first: CapabilityData, second: CapabilityData) -> Result<CapabilityData> {
+    match (first, second) {
+        (CapabilityData::AssignedPcr(mut first), CapabilityData::AssignedPcr(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::AssignedPcr(first))
+        }
+        (CapabilityData::Handles(mut first), CapabilityData::Handles(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::Handles(first))
+        }
+        (CapabilityData::Algorithms(mut first), CapabilityData::Algorithms(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::Algorithms(first))
+        }
+        (CapabilityData::Command(mut first), CapabilityData::Command(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::Command(first))
+        }
+        (CapabilityData::EccCurves(mut first), CapabilityData::EccCurves(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::EccCurves(first))
+        }
+        (CapabilityData::TpmProperties(mut first), CapabilityData::TpmProperties(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::TpmProperties(first))
+        }
+        (CapabilityData::PcrProperties(mut first), CapabilityData::PcrProperties(second)) => {
+            first.extend(second);
+            Ok(CapabilityData::PcrProperties(first))
+        }
+        _ => {
+            error!("TPM returned capability data of mismatched types across pages");
+            Err(Error::WrapperError(ErrorKind::WrongValueFromTpm))
+        }
+    }
 }
@@ -0,0 +1,126 @@
+// Copyright 2021 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use crate::common::create_ctx_with_session;
+use tss_esapi::{
+    constants::CapabilityType,
+    structures::{CapabilityData, PropertyTag, PublicParameters, RsaExponent},
+};
+
+#[test]
+fn test_get_capability_all() {
+    let mut context = create_ctx_with_session();
+
+    // `TpmProperties` is used here; the `get_capability_all` pagination
+    // loop only goes past the first page if the TPM reports more
+    // fixed/variable properties than fit in one page.
+    let capabilities = context
+        .get_capability_all(CapabilityType::TpmProperties, 0)
+        .expect("Failed to call get_capability_all");
+
+    match capabilities {
+        CapabilityData::TpmProperties(properties) => assert!(!properties.is_empty()),
+        _ => panic!("get_capability_all did not return TPM properties"),
+    }
+}
+
+#[test]
+fn test_get_capability_all_matches_manual_pagination() {
+    let mut context = create_ctx_with_session();
+
+    let mut expected_count = 0;
+    let mut property = 0;
+    loop {
+        let (capabilities, more_data) = context
+            .get_capability(CapabilityType::TpmProperties, property, 128)
+            .expect("Failed to call get_capability");
+        let properties = match capabilities {
+            CapabilityData::TpmProperties(properties) => properties,
+            _ => panic!("get_capability did not return TPM properties"),
+        };
+        expected_count += properties.len();
+        if !more_data {
+            break;
+        }
+        property = u32::from(
+            properties
+                .iter()
+                .last()
+                .expect("TPM reported more data without returning any properties")
+                .property(),
+        ) + 1;
+    }
+
+    let capabilities = context
+        .get_capability_all(CapabilityType::TpmProperties, 0)
+        .expect("Failed to call get_capability_all");
+
+    match capabilities {
+        CapabilityData::TpmProperties(properties) => assert_eq!(properties.len(), expected_count),
+        _ => panic!("get_capability_all did not return TPM properties"),
+    }
+}
+
+#[test]
+fn test_get_tpm_property() {
+    let mut context = create_ctx_with_session();
+
+    let revision = context
+        .get_tpm_property(PropertyTag::Revision)
+        .expect("Failed to call get_tpm_property")
+        .expect("TPM did not report a revision");
+    assert_ne!(revision, 0);
+
+    let year = context
+        .get_tpm_property(PropertyTag::Year)
+        .expect("Failed to call get_tpm_property")
+        .expect("TPM did not report a year");
+    assert_ne!(year, 0);
+}
+
+#[test]
+fn test_get_tpm_info() {
+    let mut context = create_ctx_with_session();
+
+    let tpm_info = context.get_tpm_info().expect("Failed to call get_tpm_info");
+
+    // A manufacturer code shorter than 4 characters is padded with null
+    // bytes, which are not part of the decoded string.
+    assert!((1..=4).contains(&tpm_info.manufacturer().len()));
+    assert!(!tpm_info.supported_algorithms().is_empty());
+}
+
+#[test]
+fn test_are_parms_supported_and_first_supported_parms() {
+    let mut context = create_ctx_with_session();
+
+    let rsa_2048 = PublicParameters::Rsa(
+        tss_esapi::structures::PublicRsaParametersBuilder::new_unrestricted_signing_key(
+            tss_esapi::interface_types::algorithm::RsaSignatureScheme::RsaSsa(
+                tss_esapi::structures::HashScheme::new(
+                    tss_esapi::interface_types::algorithm::HashingAlgorithm::Sha256,
+                ),
+            ),
+            tss_esapi::interface_types::key_bits::RsaKeyBits::Rsa2048,
+            RsaExponent::default(),
+        )
+        .build()
+        .expect("Failed to build RSA 2048 parameters"),
+    );
+
+    assert!(context
+        .are_parms_supported(rsa_2048.clone())
+        .expect("Failed to call are_parms_supported"));
+
+    let supported = context
+        .first_supported_parms(vec![rsa_2048])
+        .expect("Failed to call first_supported_parms");
+    assert!(supported.is_some());
+}
+
+#[test]
+fn test_get_pcr_banks() {
+    let mut context = create_ctx_with_session();
+
+    let pcr_banks = context.get_pcr_banks().expect("Failed to call get_pcr_banks");
+    assert!(!pcr_banks.is_empty());
+}